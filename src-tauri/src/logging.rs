@@ -0,0 +1,120 @@
+//! File-backed logger for the desktop shell. Packaged builds run with
+//! `windows_subsystem = "windows"`, so there's no console to catch
+//! `eprintln!` output — this gives boot/health/crash diagnostics a home a
+//! bug report can actually attach.
+
+use anyhow::{anyhow, Context, Result};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+/// Rotate once the active log file crosses this size.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated backups (`hudson.log.1` .. `hudson.log.N`) to keep.
+const MAX_BACKUPS: u32 = 3;
+
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+struct FileLogger {
+    file: Mutex<File>,
+    path: PathBuf,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "[{} {}] {}\n",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        eprint!("{line}");
+
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+            let _ = file.flush();
+        }
+        self.rotate_if_needed();
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+impl FileLogger {
+    fn rotate_if_needed(&self) {
+        if fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0) < MAX_LOG_BYTES {
+            return;
+        }
+
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+
+        // Re-check under the lock in case another thread already rotated.
+        if fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0) < MAX_LOG_BYTES {
+            return;
+        }
+
+        for index in (1..MAX_BACKUPS).rev() {
+            let _ = fs::rename(backup_path(&self.path, index), backup_path(&self.path, index + 1));
+        }
+        let _ = fs::rename(&self.path, backup_path(&self.path, 1));
+
+        if let Ok(rotated) = OpenOptions::new().create(true).append(true).open(&self.path) {
+            *file = rotated;
+        }
+    }
+}
+
+fn backup_path(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{index}"));
+    path.with_file_name(name)
+}
+
+/// Initializes the process-wide logger: stderr plus a size-rotated file
+/// under the platform log directory. Returns the active log file path so
+/// the frontend can offer "open logs" / "copy diagnostics". Must only be
+/// called once per process.
+pub fn init(app: &tauri::AppHandle<tauri::Wry>) -> Result<PathBuf> {
+    let dir = tauri::api::path::app_log_dir(&app.config()).unwrap_or_else(std::env::temp_dir);
+    fs::create_dir_all(&dir).context("failed to create log directory")?;
+    let path = dir.join("hudson.log");
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open log file at {path:?}"))?;
+
+    log::set_boxed_logger(Box::new(FileLogger {
+        file: Mutex::new(file),
+        path: path.clone(),
+    }))
+    .map_err(|err| anyhow!("logger already initialized: {err}"))?;
+    log::set_max_level(LevelFilter::Info);
+
+    let _ = LOG_PATH.set(path.clone());
+    Ok(path)
+}
+
+/// Returns the active log file path, for the `log_file_path` command.
+pub fn log_path() -> Option<PathBuf> {
+    LOG_PATH.get().cloned()
+}
@@ -1,19 +1,46 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod logging;
+
 use anyhow::{anyhow, Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
     fs,
-    path::PathBuf,
-    process::{Child, Command, Stdio},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, ChildStderr, ChildStdout, Command, Stdio},
     sync::Arc,
     time::Duration,
 };
 use tauri::{AppHandle, Manager, State, WindowEvent, Wry};
-use tokio::{sync::Mutex, time::sleep};
+use tokio::{
+    sync::{Mutex, Notify},
+    time::sleep,
+};
+
+/// Initial backoff delay before the first restart attempt; doubles each
+/// subsequent attempt up to `RESTART_BACKOFF_CAP`.
+const RESTART_BACKOFF_FLOOR: Duration = Duration::from_millis(250);
+const RESTART_BACKOFF_CAP: Duration = Duration::from_secs(8);
+const RESTART_MAX_ATTEMPTS: u32 = 6;
+
+/// How long `terminate_backend` waits for an orderly exit after requesting
+/// one before giving up and sending `kill()`.
+const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 struct BackendState {
-    child: Arc<Mutex<Option<Child>>>,
+    child: Arc<Mutex<Option<RunningBackend>>>,
+    generation: Arc<Mutex<u64>>,
+    /// Wakes the single `supervise_backend` task to restart on demand,
+    /// instead of booting a second supervisor alongside it.
+    restart_requested: Arc<Notify>,
+}
+
+/// A spawned backend process plus the path it was launched from, which
+/// `terminate_backend` needs to pick the right graceful-stop mechanism.
+struct RunningBackend {
+    child: Child,
+    executable: PathBuf,
 }
 
 #[derive(Deserialize)]
@@ -21,6 +48,29 @@ struct Handshake {
     port: u16,
 }
 
+/// Payload for the `backend://status` event the LiveView subscribes to.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+enum BackendStatus {
+    Starting { attempt: u32 },
+    Healthy { port: u16, generation: u64 },
+    Crashed { attempt: u32 },
+    GivingUp,
+}
+
+fn emit_status(app: &AppHandle<Wry>, status: BackendStatus) {
+    if let Err(err) = app.emit_all("backend://status", status) {
+        log::warn!("Failed to emit backend status event: {err:?}");
+    }
+}
+
+#[tauri::command]
+fn log_file_path() -> Result<String, String> {
+    logging::log_path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| "logger not initialized".to_string())
+}
+
 #[tauri::command]
 async fn shutdown_backend(state: State<'_, BackendState>) -> Result<(), String> {
     terminate_backend(Arc::clone(&state.child))
@@ -28,14 +78,34 @@ async fn shutdown_backend(state: State<'_, BackendState>) -> Result<(), String>
         .map_err(|err| err.to_string())
 }
 
+/// Asks the single running `supervise_backend` task to restart the backend,
+/// rather than terminating it here and booting a second supervisor — two
+/// supervisors racing `start_and_adopt_backend` would spawn two BEAM nodes
+/// against the same SQLite data directory.
+#[tauri::command]
+fn restart_backend(state: State<'_, BackendState>) -> Result<(), String> {
+    state.restart_requested.notify_one();
+    Ok(())
+}
+
 fn main() {
     tauri::Builder::default()
         .manage(BackendState {
             child: Arc::new(Mutex::new(None)),
+            generation: Arc::new(Mutex::new(0)),
+            restart_requested: Arc::new(Notify::new()),
         })
         .setup(|app| {
             let app_handle = app.handle();
-            let state = Arc::clone(&app.state::<BackendState>().child);
+
+            if let Err(err) = logging::init(&app_handle) {
+                eprintln!("Failed to initialize logging: {err:?}");
+            }
+
+            let state = app.state::<BackendState>();
+            let child_state = Arc::clone(&state.child);
+            let generation_state = Arc::clone(&state.generation);
+            let restart_requested = Arc::clone(&state.restart_requested);
 
             tauri::WindowBuilder::new(
                 app,
@@ -48,8 +118,11 @@ fn main() {
             .build()?;
 
             tauri::async_runtime::spawn(async move {
-                if let Err(err) = boot_sequence(app_handle, state).await {
-                    eprintln!("Backend boot failed: {err:?}");
+                if let Err(err) =
+                    boot_sequence(app_handle, child_state, generation_state, restart_requested)
+                        .await
+                {
+                    log::error!("Backend boot failed: {err:?}");
                 }
             });
             Ok(())
@@ -62,45 +135,200 @@ fn main() {
                 });
             }
         })
-        .invoke_handler(tauri::generate_handler![shutdown_backend])
+        .invoke_handler(tauri::generate_handler![
+            shutdown_backend,
+            restart_backend,
+            log_file_path
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
-async fn boot_sequence(app: AppHandle<Wry>, state: Arc<Mutex<Option<Child>>>) -> Result<()> {
-    eprintln!("Boot sequence started");
+/// Spawns the backend, waits for it to become healthy, points the window at
+/// it, then hands off to `supervise_backend` for the lifetime of the app.
+async fn boot_sequence(
+    app: AppHandle<Wry>,
+    state: Arc<Mutex<Option<RunningBackend>>>,
+    generation: Arc<Mutex<u64>>,
+    restart_requested: Arc<Notify>,
+) -> Result<()> {
+    log::info!("Boot sequence started");
 
     let resource_dir = tauri::api::path::resource_dir(app.package_info(), &app.env());
-    let child = spawn_backend(resource_dir).context("failed to launch BEAM sidecar")?;
-    eprintln!("Backend spawned");
+    let port = start_and_adopt_backend(&app, &resource_dir, &state, 1).await?;
+    navigate_to_backend(&app, port)?;
+
+    let gen = {
+        let mut guard = generation.lock().await;
+        *guard += 1;
+        *guard
+    };
+    emit_status(&app, BackendStatus::Healthy { port, generation: gen });
+
+    tauri::async_runtime::spawn(supervise_backend(
+        app,
+        resource_dir,
+        state,
+        generation,
+        restart_requested,
+    ));
 
-    let port = wait_for_port_file().await?;
-    eprintln!("Got port from handshake: {}", port);
-
-    wait_for_health(port).await?;
-    eprintln!("Health check passed");
+    Ok(())
+}
 
+/// Spawns the backend binary, waits for the handshake and health check, and
+/// stores the resulting `Child` in shared state. Emits a `Starting` event
+/// before the attempt so the frontend can show progress.
+///
+/// The child is adopted into `state` immediately after spawning, *before*
+/// the handshake/health wait (which can take several seconds) — that way it
+/// can always be found and killed: by the teardown below if it fails the
+/// handshake/health check (so it doesn't keep running as an orphan with the
+/// next attempt's port/SQLite data dir), or by `terminate_backend` if the
+/// window closes mid-boot.
+async fn start_and_adopt_backend(
+    app: &AppHandle<Wry>,
+    resource_dir: &Option<PathBuf>,
+    state: &Arc<Mutex<Option<RunningBackend>>>,
+    attempt: u32,
+) -> Result<u16> {
+    emit_status(app, BackendStatus::Starting { attempt });
+
+    let (mut child, executable) =
+        spawn_backend(resource_dir.clone()).context("failed to launch BEAM sidecar")?;
+    log::info!("Backend spawned");
+
+    let stdout = child.stdout.take();
     {
         let mut guard = state.lock().await;
-        *guard = Some(child);
+        *guard = Some(RunningBackend { child, executable });
+    }
+
+    let result: Result<u16> = async {
+        let port = match stdout {
+            Some(stdout) => wait_for_handshake(stdout).await?,
+            None => wait_for_port_file().await?,
+        };
+        log::info!("Got port from handshake: {}", port);
+
+        wait_for_health(port).await?;
+        log::info!("Health check passed");
+
+        Ok(port)
     }
+    .await;
 
+    if result.is_err() {
+        // Handshake or health check failed (or the child was already taken
+        // and killed by a concurrent `terminate_backend`, e.g. on window
+        // close). Tear down whatever's left in `state` rather than leaving
+        // it running for the next attempt to collide with.
+        let mut guard = state.lock().await;
+        if let Some(RunningBackend { mut child, .. }) = guard.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    result
+}
+
+fn navigate_to_backend(app: &AppHandle<Wry>, port: u16) -> Result<()> {
     if let Some(window) = app.get_window("main") {
-        eprintln!("Navigating window to http://127.0.0.1:{}", port);
+        log::info!("Navigating window to http://127.0.0.1:{}", port);
         window
             .eval(&format!(
                 "window.location.replace('http://127.0.0.1:{port}');"
             ))
             .context("failed to load LiveView into WebView")?;
-        eprintln!("Navigation command sent");
+        log::info!("Navigation command sent");
+        Ok(())
     } else {
-        return Err(anyhow!("Main window missing"));
+        Err(anyhow!("Main window missing"))
     }
+}
 
-    Ok(())
+/// The single long-lived backend watchdog. Polls the supervised child with
+/// `try_wait`; on unexpected exit, restarts it with exponential backoff
+/// (capped) up to `RESTART_MAX_ATTEMPTS`. Also listens for `restart_requested`
+/// so `restart_backend` can trigger an immediate, *unbackoff'd* restart
+/// through this same task rather than a second one racing it for the child.
+async fn supervise_backend(
+    app: AppHandle<Wry>,
+    resource_dir: Option<PathBuf>,
+    state: Arc<Mutex<Option<RunningBackend>>>,
+    generation: Arc<Mutex<u64>>,
+    restart_requested: Arc<Notify>,
+) {
+    let mut backoff = RESTART_BACKOFF_FLOOR;
+    let mut attempt: u32 = 0;
+    // Set when a restart attempt fails; its teardown leaves `state` empty,
+    // so the next pass must retry instead of idling on a missing child.
+    let mut restart_failed = false;
+
+    loop {
+        let exited = std::mem::take(&mut restart_failed) || {
+            let mut guard = state.lock().await;
+            match guard.as_mut() {
+                Some(running) => matches!(running.child.try_wait(), Ok(Some(_))),
+                None => false,
+            }
+        };
+
+        if !exited {
+            tokio::select! {
+                _ = sleep(Duration::from_millis(500)) => {
+                    attempt = 0;
+                    backoff = RESTART_BACKOFF_FLOOR;
+                    continue;
+                }
+                _ = restart_requested.notified() => {
+                    log::info!("Manual backend restart requested");
+                    if let Err(err) = terminate_backend(Arc::clone(&state)).await {
+                        log::error!("Failed to stop backend for restart: {err:?}");
+                    }
+                    attempt = 0;
+                    backoff = RESTART_BACKOFF_FLOOR;
+                }
+            }
+        } else {
+            attempt += 1;
+            log::warn!("Backend exited unexpectedly (attempt {attempt})");
+            emit_status(&app, BackendStatus::Crashed { attempt });
+
+            if attempt > RESTART_MAX_ATTEMPTS {
+                log::error!("Giving up after {attempt} restart attempts");
+                emit_status(&app, BackendStatus::GivingUp);
+                return;
+            }
+
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(RESTART_BACKOFF_CAP);
+        }
+
+        match start_and_adopt_backend(&app, &resource_dir, &state, attempt).await {
+            Ok(port) => {
+                if let Err(err) = navigate_to_backend(&app, port) {
+                    log::error!("Failed to re-navigate after restart: {err:?}");
+                }
+                let gen = {
+                    let mut guard = generation.lock().await;
+                    *guard += 1;
+                    *guard
+                };
+                emit_status(&app, BackendStatus::Healthy { port, generation: gen });
+                attempt = 0;
+                backoff = RESTART_BACKOFF_FLOOR;
+            }
+            Err(err) => {
+                log::warn!("Restart attempt {attempt} failed: {err:?}");
+                restart_failed = true;
+            }
+        }
+    }
 }
 
-fn spawn_backend(resource_dir: Option<PathBuf>) -> Result<Child> {
+fn spawn_backend(resource_dir: Option<PathBuf>) -> Result<(Child, PathBuf)> {
     let candidates = candidate_backend_paths(resource_dir);
 
     let executable = candidates
@@ -113,7 +341,7 @@ fn spawn_backend(resource_dir: Option<PathBuf>) -> Result<Child> {
         .map(|value| value.split_whitespace().map(String::from).collect())
         .unwrap_or_else(|_| default_args_for(&executable));
 
-    let mut command = Command::new(executable);
+    let mut command = Command::new(&executable);
     if !args.is_empty() {
         command.args(args);
     }
@@ -122,11 +350,116 @@ fn spawn_backend(resource_dir: Option<PathBuf>) -> Result<Child> {
     let enable_neon = std::env::var("HUDSON_ENABLE_NEON").unwrap_or_else(|_| "false".to_string());
     command.env("HUDSON_ENABLE_NEON", enable_neon);
 
-    command
+    if cfg!(target_os = "linux") {
+        normalize_linux_environment(&mut command);
+    }
+
+    // Remove any stale handshake file from a previous run so the
+    // `wait_for_port_file` fallback can't read a port that's no longer ours.
+    let _ = fs::remove_file(handshake_path());
+
+    let mut child = command
         .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
+        .stderr(Stdio::piped())
         .spawn()
-        .context("failed to spawn backend process")
+        .context("failed to spawn backend process")?;
+
+    if let Some(stderr) = child.stderr.take() {
+        spawn_backend_log_drain(stderr);
+    }
+
+    Ok((child, executable))
+}
+
+/// PATH-style environment variables that AppImage/Flatpak/Snap launchers
+/// rewrite to point at bundle-local directories before exec'ing the app.
+const BUNDLE_PATH_VARS: &[&str] = &[
+    "LD_LIBRARY_PATH",
+    "GST_PLUGIN_SYSTEM_PATH",
+    "GTK_PATH",
+    "XDG_DATA_DIRS",
+    "PATH",
+    "GIO_MODULE_DIR",
+];
+
+/// Strips bundle-local entries the AppImage/Flatpak/Snap launcher injected
+/// into PATH-style variables, so the BEAM runtime doesn't pick up bundled
+/// libraries instead of the system ones it was built against.
+///
+/// Where the launcher stashed the pre-bundle value in a `*_ORIG` variable,
+/// that is restored verbatim in preference to filtering the mutated one.
+fn normalize_linux_environment(command: &mut Command) {
+    let Some(bundle_root) = detect_bundle_root() else {
+        return;
+    };
+
+    for var in BUNDLE_PATH_VARS {
+        if let Ok(orig) = std::env::var(format!("{var}_ORIG")) {
+            set_or_remove(command, var, orig);
+            continue;
+        }
+
+        let Ok(current) = std::env::var(var) else {
+            continue;
+        };
+
+        let cleaned = strip_bundle_entries(&current, &bundle_root);
+        set_or_remove(command, var, cleaned);
+    }
+}
+
+/// Returns the bundle mount root if we're running under a packaged launcher
+/// (AppImage, Flatpak, or Snap), otherwise `None`.
+///
+/// `APPIMAGE` is deliberately not used as a root: it points at the
+/// `.AppImage` file itself (commonly outside the mount, e.g. in
+/// `~/Downloads`), not the `/tmp/.mount_*` directory the runtime extracts
+/// into, so entries injected under the mount would never match it as a
+/// prefix. `APPDIR` is the mount directory and is what the AppImage runtime
+/// always sets alongside `APPIMAGE`, so it's the reliable signal here.
+fn detect_bundle_root() -> Option<PathBuf> {
+    if let Ok(appdir) = std::env::var("APPDIR") {
+        return Some(PathBuf::from(appdir));
+    }
+    if std::env::var("FLATPAK_ID").is_ok() {
+        return Some(PathBuf::from("/app"));
+    }
+    if let Ok(snap) = std::env::var("SNAP") {
+        return Some(PathBuf::from(snap));
+    }
+    None
+}
+
+/// Splits a `:`-joined PATH-style value, drops empty entries and any entry
+/// whose prefix lies inside `bundle_root`, then deduplicates while
+/// preferring the *later* (lower-priority, typically system) occurrence of
+/// a repeated entry.
+fn strip_bundle_entries(value: &str, bundle_root: &Path) -> String {
+    let mut kept = Vec::new();
+
+    for entry in value.split(':') {
+        if entry.is_empty() {
+            continue;
+        }
+        if PathBuf::from(entry).starts_with(bundle_root) {
+            continue;
+        }
+        kept.retain(|existing| existing != &entry);
+        kept.push(entry);
+    }
+
+    kept.join(":")
+}
+
+/// Sets `var` on `command` to `value`, or `env_remove`s it if `value` ended
+/// up empty — an empty value has different semantics than an unset one for
+/// variables like `LD_LIBRARY_PATH`.
+fn set_or_remove(command: &mut Command, var: &str, value: String) {
+    if value.is_empty() {
+        command.env_remove(var);
+    } else {
+        command.env(var, value);
+    }
 }
 
 fn candidate_backend_paths(resource_dir: Option<PathBuf>) -> Vec<PathBuf> {
@@ -195,6 +528,67 @@ fn default_backend_path() -> String {
     }
 }
 
+/// Watches the backend's piped stdout for a handshake line (a JSON object
+/// matching `Handshake`, e.g. `{"port":4000}`), reading on a blocking thread
+/// since `BufReader::read_line` isn't async. Falls back to the port-file
+/// handshake if nothing arrives within the timeout.
+///
+/// The reader thread keeps draining stdout after the handshake is found so
+/// the backend never blocks writing to a full pipe buffer.
+async fn wait_for_handshake(stdout: ChildStdout) -> Result<u16> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        let mut tx = Some(tx);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    let trimmed = line.trim();
+                    let handshake = tx
+                        .take()
+                        .map(|sender| (sender, serde_json::from_str::<Handshake>(trimmed)));
+
+                    match handshake {
+                        Some((sender, Ok(handshake))) => {
+                            let _ = sender.send(handshake.port);
+                        }
+                        Some((sender, Err(_))) => {
+                            tx = Some(sender);
+                            log::info!("backend: {trimmed}");
+                        }
+                        None => log::info!("backend: {trimmed}"),
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+
+    match tokio::time::timeout(Duration::from_secs(10), rx).await {
+        Ok(Ok(port)) => Ok(port),
+        _ => wait_for_port_file().await,
+    }
+}
+
+/// Reads the backend's piped stderr line-by-line on a blocking thread and
+/// forwards each line into the log with a `backend:` prefix, so crash
+/// diagnostics land in the same file as the launcher's own messages.
+fn spawn_backend_log_drain(stderr: ChildStderr) {
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stderr);
+        for line in reader.lines().flatten() {
+            log::info!("backend: {line}");
+        }
+    });
+}
+
+/// Fallback handshake path for when the stdout-based handshake doesn't
+/// arrive in time: polls the handshake file the backend also writes on boot.
 async fn wait_for_port_file() -> Result<u16> {
     let path = handshake_path();
     for _ in 0..50 {
@@ -242,12 +636,90 @@ async fn wait_for_health(port: u16) -> Result<()> {
     Err(anyhow!("Timed out waiting for /healthz on {url}"))
 }
 
-async fn terminate_backend(state: Arc<Mutex<Option<Child>>>) -> Result<()> {
-    let mut guard = state.lock().await;
-    if let Some(mut child) = guard.take() {
+/// Gracefully stops the backend, falling back to a hard `kill()` only if it
+/// hasn't exited within `GRACEFUL_SHUTDOWN_TIMEOUT`. This gives the Erlang
+/// VM a chance to flush SQLite writes and run its own shutdown hooks instead
+/// of being SIGKILLed outright.
+async fn terminate_backend(state: Arc<Mutex<Option<RunningBackend>>>) -> Result<()> {
+    let running = {
+        let mut guard = state.lock().await;
+        guard.take()
+    };
+
+    let Some(RunningBackend { mut child, executable }) = running else {
+        return Ok(());
+    };
+
+    request_graceful_stop(&executable, &child).await;
+
+    if !wait_for_exit(&mut child, GRACEFUL_SHUTDOWN_TIMEOUT).await {
+        log::warn!("Backend did not exit within grace period; killing");
         let _ = child.kill();
-        let _ = child.wait();
     }
+    let _ = child.wait();
 
     Ok(())
 }
+
+/// How long to wait for the graceful-stop command itself (the release's
+/// `stop` or a `kill -TERM`) before giving up on it. This is separate from
+/// `GRACEFUL_SHUTDOWN_TIMEOUT`, which bounds the *poll after* the command
+/// runs — without this, a hung `stop` would block the caller (and, via the
+/// `CloseRequested` handler's `block_on`, the whole window close) forever.
+const GRACEFUL_STOP_COMMAND_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Asks the backend to stop on its own terms: the release's own `stop`
+/// command for a `_build/prod/rel` install, or SIGTERM to the `burrito_out`
+/// binary's pid otherwise. Runs the (blocking) command on a blocking-pool
+/// thread under a timeout, so a hung command can't stall the tokio runtime
+/// or the caller.
+async fn request_graceful_stop(executable: &Path, child: &Child) {
+    let executable = executable.to_path_buf();
+    let is_release = !executable.to_string_lossy().contains("burrito_out");
+    let pid = child.id();
+
+    let command = tokio::task::spawn_blocking(move || {
+        if is_release {
+            let _ = Command::new(&executable).arg("stop").status();
+        } else {
+            send_sigterm(pid);
+        }
+    });
+
+    if tokio::time::timeout(GRACEFUL_STOP_COMMAND_TIMEOUT, command)
+        .await
+        .is_err()
+    {
+        log::warn!(
+            "Graceful-stop command did not finish within {:?}; proceeding to the kill fallback",
+            GRACEFUL_STOP_COMMAND_TIMEOUT
+        );
+    }
+}
+
+#[cfg(unix)]
+fn send_sigterm(pid: u32) {
+    let _ = Command::new("kill").arg("-TERM").arg(pid.to_string()).status();
+}
+
+#[cfg(not(unix))]
+fn send_sigterm(_pid: u32) {
+    // No SIGTERM equivalent on Windows; `terminate_backend` falls back to
+    // `kill()` once the grace period elapses.
+}
+
+/// Polls `child` with `try_wait` until it exits or `timeout` elapses.
+/// Returns whether it exited in time.
+async fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) | Err(_) => return true,
+            Ok(None) => {}
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+}